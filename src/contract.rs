@@ -0,0 +1,35 @@
+//! Lightweight design-by-contract helpers.
+//!
+//! A full `#[requires]`/`#[ensures]`/`#[invariant]` attribute-macro crate
+//! would pull in its own dependency; these three macros give the same
+//! shape using nothing but `assert!`: a precondition, postcondition or
+//! invariant violation panics immediately at the `on_msg` call that broke
+//! it, instead of only showing up later as a failed global `stateright`
+//! property with a much longer counterexample trace. They use `assert!`
+//! rather than `debug_assert!` because the checker is normally run in
+//! release mode for throughput, and a contract that's compiled out under
+//! `--release` never actually catches anything there.
+
+/// A precondition a function's caller must uphold.
+#[macro_export]
+macro_rules! requires {
+    ($cond:expr, $msg:expr) => {
+        assert!($cond, concat!("precondition violated: ", $msg));
+    };
+}
+
+/// A postcondition a function must uphold before returning.
+#[macro_export]
+macro_rules! ensures {
+    ($cond:expr, $msg:expr) => {
+        assert!($cond, concat!("postcondition violated: ", $msg));
+    };
+}
+
+/// A state invariant that must hold whenever a state transition settles.
+#[macro_export]
+macro_rules! invariant {
+    ($cond:expr, $msg:expr) => {
+        assert!($cond, concat!("invariant violated: ", $msg));
+    };
+}
@@ -0,0 +1,65 @@
+//! A deliberately weak, deterministic stand-in for threshold BLS signatures.
+//!
+//! Real elder signatures would be threshold BLS shares that cannot be forged
+//! without the corresponding secret key share. Carrying that crypto into the
+//! model would blow up the state space for no benefit, so instead a
+//! `SignatureShare` only records *who* claims to have signed, not a binding
+//! over the signed content. This is intentional: it mirrors the same
+//! weakness a compromised or Byzantine elder would exploit in practice, and
+//! lets the checker explore forged/equivocating signatures cheaply.
+
+use std::collections::BTreeSet;
+
+use stateright::actor::Id;
+
+/// Returns true once `count` out of `total` constitutes a BFT supermajority
+/// (more than two thirds).
+pub fn is_super_majority(count: usize, total: usize) -> bool {
+    count * 3 > total * 2
+}
+
+/// A share-holder's identity. Because signatures aren't actually bound to
+/// the signed message, holding a `SecretKeyShare` for `id` is equivalent to
+/// being able to forge a vote on `id`'s behalf.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct SecretKeyShare(pub Id);
+
+impl SecretKeyShare {
+    /// "Signs" `msg`. The message is accepted only to keep the call site
+    /// honest about what's being voted on; it is not checked by `verify`.
+    pub fn sign<T>(&self, _msg: &T) -> SignatureShare {
+        SignatureShare { signer: self.0 }
+    }
+}
+
+/// One elder's vote. Unforgeable in a real system, trivially forgeable here
+/// by anyone who knows `signer`'s `Id`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct SignatureShare {
+    pub signer: Id,
+}
+
+/// An aggregated signature: the set of elders whose shares were combined.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Signature {
+    pub voters: BTreeSet<Id>,
+}
+
+impl Signature {
+    pub fn aggregate(shares: impl IntoIterator<Item = SignatureShare>) -> Self {
+        Self {
+            voters: shares.into_iter().map(|s| s.signer).collect(),
+        }
+    }
+
+    /// Verifies that `voters` forms a supermajority of `elders`.
+    ///
+    /// `msg` is unused: this scheme never binds a signature to the content
+    /// it was supposedly cast over, so a `Signature` can be "verified"
+    /// against any message, including one the voters never saw.
+    pub fn verify<T>(&self, elders: &BTreeSet<Id>, _msg: &T) -> bool {
+        !self.voters.is_empty()
+            && self.voters.is_subset(elders)
+            && is_super_majority(self.voters.len(), elders.len())
+    }
+}
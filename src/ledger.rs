@@ -0,0 +1,312 @@
+//! Reissue: elders co-sign a [`Tx`] that spends `inputs` into `outputs` of
+//! equal total value, the same aggregate-signature pattern used by
+//! [`crate::membership`]. Double-spend prevention doesn't rely solely on
+//! elders observing each other's pending votes: every coin also carries a
+//! [`Commitment`]/[`Nullifier`] pair, so a spend is only ever valid once
+//! per coin regardless of how votes interleave.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::{Hash, Hasher};
+
+use stateright::actor::{Id, Out};
+
+use crate::fake_crypto::{is_super_majority, SecretKeyShare, Signature, SignatureShare};
+use crate::Node;
+
+pub type Amount = u64;
+
+fn hash_of<T: Hash>(t: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    t.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Fills 32 bytes deterministically from `seed`, standing in for a real
+/// hash-to-bytes function. Weak, but sufficient to keep the model's
+/// commitments/nullifiers collision-free for the `Id`s it ever sees.
+fn derive32<T: Hash>(seed: &T) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    for (chunk_index, chunk) in out.chunks_mut(8).enumerate() {
+        chunk.copy_from_slice(&hash_of(&(hash_of(seed), chunk_index)).to_le_bytes());
+    }
+    out
+}
+
+/// A coin's public commitment, published when it is created:
+/// `H("commit" || pk || value || nonce)`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct Commitment(u64);
+
+/// A coin's spend marker, published when it is spent:
+/// `H("nullify" || sk || nonce)`. Knowing `sk` is exactly what lets a
+/// spend be linked back to its commitment without revealing which
+/// commitment it is.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct Nullifier(u64);
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct Dbc {
+    sk: [u8; 32],
+    nonce: [u8; 32],
+    amount: Amount,
+}
+
+impl Dbc {
+    pub fn amount(&self) -> Amount {
+        self.amount
+    }
+
+    fn pk(&self) -> [u8; 32] {
+        derive32(&("pk", self.sk))
+    }
+
+    pub fn commitment(&self) -> Commitment {
+        Commitment(hash_of(&("commit", self.pk(), self.amount, self.nonce)))
+    }
+
+    pub fn nullifier(&self) -> Nullifier {
+        Nullifier(hash_of(&("nullify", self.sk, self.nonce)))
+    }
+
+    /// Deterministically rolls the nonce forward, producing the fresh
+    /// child coin a spend gives rise to.
+    pub fn evolve(&self) -> Self {
+        Self {
+            sk: self.sk,
+            nonce: derive32(&("coin-evolve", self.sk, self.nonce)),
+            amount: self.amount,
+        }
+    }
+}
+
+/// Output dbc keys are derived from their inputs, amount and position so
+/// that every honest node proposing the same reissue agrees on the same
+/// output coins without any shared mutable counter.
+fn derive_output(inputs: &[Dbc], amount: Amount, index: usize) -> Dbc {
+    Dbc {
+        sk: derive32(&("output-sk", inputs, amount, index)),
+        nonce: derive32(&("output-nonce", inputs, amount, index)),
+        amount,
+    }
+}
+
+/// Builds the `Tx` a reissue of `inputs` into `output_amounts` would produce,
+/// without broadcasting anything. Factored out of [`Wallet::reissue`] so an
+/// adversary can construct a conflicting `Tx` over the same inputs to forge
+/// votes for directly, bypassing `ReqReissue`/`VoteReissue` entirely.
+pub(crate) fn build_tx(inputs: Vec<Dbc>, output_amounts: Vec<Amount>) -> Tx {
+    // A single input reissued into a single output of the same value is
+    // just that coin moving forward in time: evolve its nonce rather than
+    // hashing a fresh, unrelated output coin.
+    let outputs = match (inputs.as_slice(), output_amounts.as_slice()) {
+        ([input], [amount]) if *amount == input.amount() => vec![input.evolve()],
+        _ => output_amounts
+            .iter()
+            .enumerate()
+            .map(|(i, &amount)| derive_output(&inputs, amount, i))
+            .collect(),
+    };
+
+    Tx { inputs, outputs }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct Tx {
+    pub inputs: Vec<Dbc>,
+    pub outputs: Vec<Dbc>,
+}
+
+/// A finalized reissue, kept around as evidence that its inputs are spent.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct SpendProof {
+    pub tx: Tx,
+    pub sig: Signature,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Msg {
+    ReqReissue(Tx),
+    VoteReissue { tx: Tx, sig_share: SignatureShare },
+    /// Gossiped (or sent in reply to a conflicting `ReqReissue`) so nodes
+    /// can reconcile their spend books without re-running consensus.
+    SpendBook(BTreeMap<Nullifier, SpendProof>),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Ledger {
+    pub genesis_dbc: Dbc,
+    unspent: BTreeSet<Dbc>,
+    /// Every input this node has seen spent, keyed by its nullifier and
+    /// mapping to the spend proof that retired it. A reissue is only
+    /// valid if none of its inputs already have an entry here.
+    spend_book: BTreeMap<Nullifier, SpendProof>,
+}
+
+impl Ledger {
+    pub fn genesis_amount(&self) -> Amount {
+        self.genesis_dbc.amount()
+    }
+
+    pub fn sum_unspent_outputs(&self) -> Amount {
+        self.unspent.iter().map(Dbc::amount).sum()
+    }
+
+    pub fn is_unspent(&self, dbc: &Dbc) -> bool {
+        self.unspent.contains(dbc) && !self.spend_book.contains_key(&dbc.nullifier())
+    }
+
+    pub fn spend_proof(&self, nullifier: Nullifier) -> Option<&SpendProof> {
+        self.spend_book.get(&nullifier)
+    }
+
+    pub fn spend_book_entries(&self) -> impl Iterator<Item = (&Nullifier, &SpendProof)> {
+        self.spend_book.iter()
+    }
+
+    fn record_spend(&mut self, nullifier: Nullifier, proof: SpendProof) {
+        self.spend_book.entry(nullifier).or_insert(proof);
+    }
+
+    fn merge_spend_book(&mut self, entries: BTreeMap<Nullifier, SpendProof>) {
+        for (nullifier, proof) in entries {
+            self.record_spend(nullifier, proof);
+        }
+    }
+
+    /// True if every unspent coin has a distinct commitment, i.e. each
+    /// live coin is tracked exactly once.
+    pub fn has_unique_unspent_commitments(&self) -> bool {
+        let mut seen = BTreeSet::new();
+        self.unspent.iter().all(|dbc| seen.insert(dbc.commitment()))
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Wallet {
+    pub ledger: Ledger,
+    pub pending_tx: Option<(Tx, Signature)>,
+    votes: BTreeMap<Tx, BTreeSet<Id>>,
+}
+
+impl Wallet {
+    pub fn new(genesis_nodes: &BTreeSet<Id>) -> Self {
+        let genesis_dbc = Dbc {
+            sk: derive32(&("genesis-sk", genesis_nodes)),
+            nonce: derive32(&("genesis-nonce", genesis_nodes)),
+            amount: genesis_nodes.len() as Amount * 1_000,
+        };
+
+        let mut unspent = BTreeSet::new();
+        unspent.insert(genesis_dbc);
+
+        Self {
+            ledger: Ledger {
+                genesis_dbc,
+                unspent,
+                spend_book: BTreeMap::new(),
+            },
+            pending_tx: None,
+            votes: BTreeMap::new(),
+        }
+    }
+
+    pub fn reissue(
+        &self,
+        elders: &BTreeSet<Id>,
+        inputs: Vec<Dbc>,
+        output_amounts: Vec<Amount>,
+        o: &mut Out<Node>,
+    ) {
+        crate::requires!(
+            inputs.iter().map(Dbc::amount).sum::<Amount>() == output_amounts.iter().sum::<Amount>(),
+            "reissue: input amounts must sum to output amounts"
+        );
+        crate::requires!(
+            inputs.iter().all(|i| self.ledger.is_unspent(i)),
+            "reissue: no input may already be spent"
+        );
+
+        o.broadcast(elders, &Msg::ReqReissue(build_tx(inputs, output_amounts)).into());
+    }
+
+    pub fn on_msg(
+        &mut self,
+        elders: &BTreeSet<Id>,
+        id: Id,
+        src: Id,
+        msg: Msg,
+        o: &mut Out<Node>,
+    ) {
+        match msg {
+            Msg::ReqReissue(tx) => {
+                let balanced = tx.inputs.iter().map(Dbc::amount).sum::<Amount>()
+                    == tx.outputs.iter().map(Dbc::amount).sum::<Amount>();
+
+                if !elders.contains(&id) || !balanced {
+                    return;
+                }
+
+                let already_spent = tx.inputs.iter().find_map(|i| {
+                    let nullifier = i.nullifier();
+                    self.ledger
+                        .spend_proof(nullifier)
+                        .map(|proof| (nullifier, proof.clone()))
+                });
+
+                if let Some((nullifier, proof)) = already_spent {
+                    // Don't sign a conflicting transaction: hand the
+                    // proposer the proof that this input is already spent.
+                    let entries = BTreeMap::from([(nullifier, proof)]);
+                    o.send(src, Msg::SpendBook(entries).into());
+                } else if tx.inputs.iter().all(|i| self.ledger.is_unspent(i)) {
+                    let sig_share = SecretKeyShare(id).sign(&tx);
+                    o.broadcast(elders, &Msg::VoteReissue { tx, sig_share }.into());
+                }
+            }
+            Msg::VoteReissue { tx, sig_share } => {
+                if elders.contains(&id) && tx.inputs.iter().all(|i| self.ledger.is_unspent(i)) {
+                    let voters = self.votes.entry(tx.clone()).or_default();
+                    voters.insert(sig_share.signer);
+
+                    if is_super_majority(voters.len(), elders.len()) {
+                        let sig = Signature::aggregate(
+                            voters.iter().map(|&signer| SignatureShare { signer }),
+                        );
+
+                        // Inputs are retired into the spend book before
+                        // their outputs become spendable, so no
+                        // interleaving of concurrent votes can ever
+                        // accept the same input twice.
+                        let mut new_entries = BTreeMap::new();
+                        for input in &tx.inputs {
+                            let proof = SpendProof {
+                                tx: tx.clone(),
+                                sig: sig.clone(),
+                            };
+                            self.ledger.record_spend(input.nullifier(), proof.clone());
+                            new_entries.insert(input.nullifier(), proof);
+                            self.ledger.unspent.remove(input);
+                        }
+                        for output in &tx.outputs {
+                            self.ledger.unspent.insert(*output);
+                        }
+
+                        self.pending_tx = Some((tx.clone(), sig));
+                        self.votes.remove(&tx);
+
+                        crate::ensures!(
+                            self.ledger.sum_unspent_outputs() == self.ledger.genesis_amount(),
+                            "reissue: ledger must stay balanced after a reissue settles"
+                        );
+
+                        o.broadcast(elders, &Msg::SpendBook(new_entries).into());
+                    }
+                }
+            }
+            Msg::SpendBook(entries) => {
+                self.ledger.merge_spend_book(entries);
+            }
+        }
+    }
+}
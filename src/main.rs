@@ -1,9 +1,15 @@
+mod contract;
 mod fake_crypto;
 mod ledger;
 mod membership;
 mod stable_set;
+mod time;
 
-use std::{borrow::Cow, collections::BTreeSet, fmt::Debug};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+};
 
 use ledger::Wallet;
 use membership::Membership;
@@ -12,12 +18,48 @@ use stateright::{
     Expectation, Model,
 };
 
-const ELDER_COUNT: usize = 3;
+/// A single action a Byzantine node is permitted to take. Because
+/// [`fake_crypto`] signatures are forgeable by anyone holding the signer's
+/// `Id`, an adversary doesn't need a stolen key to misbehave convincingly.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum AdversaryAction {
+    /// Send disjoint elder subsets conflicting votes in the same round:
+    /// a second, forged membership candidate, or a second, conflicting
+    /// reissue of an already-claimed input.
+    Equivocate,
+    /// Re-emit a previously observed signed message.
+    Replay,
+    /// Swallow messages arriving from `target` instead of acting on them.
+    DropOrDelay,
+}
+
+/// Designates which `Id`s are Byzantine and what they're allowed to do,
+/// so the checker can explore executions where some nodes deviate from
+/// the honest protocol rather than only crash/reorder/duplicate.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct AdversaryCfg {
+    pub adversaries: BTreeSet<Id>,
+    pub actions: BTreeSet<AdversaryAction>,
+    pub target: Option<Id>,
+}
+
+impl AdversaryCfg {
+    fn is_adversary(&self, id: Id) -> bool {
+        self.adversaries.contains(&id)
+    }
+
+    fn allows(&self, action: AdversaryAction) -> bool {
+        self.actions.contains(&action)
+    }
+}
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct State {
     pub membership: Membership,
     pub wallet: Wallet,
+    /// Messages this actor has observed, kept only so an adversarial actor
+    /// can later replay one. Empty for honest actors.
+    adversary_log: Vec<(Id, Msg)>,
 }
 
 impl State {
@@ -30,6 +72,8 @@ impl State {
 pub struct Node {
     pub genesis_nodes: BTreeSet<Id>,
     pub peers: Vec<Id>,
+    pub adversary_cfg: AdversaryCfg,
+    pub time_cfg: time::Config,
 }
 
 #[derive(Clone, Eq, Hash, PartialEq)]
@@ -37,6 +81,8 @@ pub enum Msg {
     Membership(membership::Msg),
     Wallet(ledger::Msg),
     StartReissue,
+    /// Advances the receiving node's local slot by one.
+    Tick,
 }
 
 impl Debug for Msg {
@@ -45,6 +91,7 @@ impl Debug for Msg {
             Msg::Membership(m) => write!(f, "{m:?}"),
             Msg::Wallet(m) => write!(f, "{m:?}"),
             Msg::StartReissue => write!(f, "StartReissue"),
+            Msg::Tick => write!(f, "Tick"),
         }
     }
 }
@@ -74,13 +121,16 @@ impl Actor for Node {
             o.broadcast(&self.genesis_nodes, &membership.req_join(id).into());
         }
 
-        // if !self.genesis_nodes.contains(&id) {
-        //     o.send(id, Msg::StartReissue);
-        // }
+        if !self.genesis_nodes.contains(&id) {
+            o.send(id, Msg::StartReissue);
+        }
+
+        o.send(id, Msg::Tick);
 
         State {
             membership,
             wallet,
+            adversary_log: Vec::new(),
         }
     }
 
@@ -92,6 +142,10 @@ impl Actor for Node {
         msg: Self::Msg,
         o: &mut Out<Self>,
     ) {
+        if self.adversary_cfg.is_adversary(id) && self.on_adversary_msg(id, state, src, &msg, o) {
+            return;
+        }
+
         match msg {
             Msg::Membership(msg) => {
                 let elders = state.elders();
@@ -103,7 +157,7 @@ impl Actor for Node {
             }
             Msg::StartReissue => {
                 let elders = state.elders();
-                let input = state.wallet.ledger.genesis_dbc.clone();
+                let input = state.wallet.ledger.genesis_dbc;
 
                 let reissue_amount = (0..self.peers.len() + 1)
                     .find(|x| Id::from(*x) == id)
@@ -117,15 +171,123 @@ impl Actor for Node {
                     o,
                 );
             }
+            Msg::Tick => {
+                state.to_mut().membership.tick(&self.time_cfg);
+                if state.membership.current_slot().0 < self.time_cfg.max_slot {
+                    o.send(id, Msg::Tick);
+                }
+            }
         }
     }
 }
 
+impl Node {
+    /// Splits `elders` into two disjoint, non-empty (where possible)
+    /// subsets so an equivocating vote can be sent to one half while a
+    /// conflicting vote goes to the other.
+    fn split_elders(elders: &BTreeSet<Id>) -> (BTreeSet<Id>, BTreeSet<Id>) {
+        let half = (elders.len() / 2).max(1);
+        let mut left = BTreeSet::new();
+        let mut right = BTreeSet::new();
+        for (i, &elder) in elders.iter().enumerate() {
+            if i < half {
+                left.insert(elder);
+            } else {
+                right.insert(elder);
+            }
+        }
+        (left, right)
+    }
+
+    /// Runs this node's Byzantine behaviour, if any, for `msg`. Returns
+    /// `true` if `msg` should be swallowed rather than handled honestly.
+    fn on_adversary_msg(
+        &self,
+        _id: Id,
+        state: &mut Cow<State>,
+        src: Id,
+        msg: &Msg,
+        o: &mut Out<Self>,
+    ) -> bool {
+        let cfg = &self.adversary_cfg;
+        let elders = state.elders();
+
+        if cfg.allows(AdversaryAction::DropOrDelay) && cfg.target == Some(src) {
+            return true;
+        }
+
+        if cfg.allows(AdversaryAction::Replay) {
+            if let Some((_, replay_msg)) = state.adversary_log.first().cloned() {
+                o.broadcast(&elders, &replay_msg);
+            }
+            state.to_mut().adversary_log.push((src, msg.clone()));
+        }
+
+        if cfg.allows(AdversaryAction::Equivocate) {
+            let (left, right) = Self::split_elders(&elders);
+
+            match msg {
+                Msg::Membership(membership::Msg::ReqJoin(candidate)) => {
+                    let forged_candidate = Id::from(usize::MAX);
+
+                    // `fake_crypto` shares aren't bound to who actually cast
+                    // them (see its module doc), so the adversary doesn't
+                    // need the other elders' cooperation to reach quorum: it
+                    // forges every elder's share on both candidates, giving
+                    // `left` and `right` each an independent supermajority
+                    // regardless of elder count.
+                    for &signer in &elders {
+                        let vote = membership::VoteJoin {
+                            candidate: *candidate,
+                            sig_share: fake_crypto::SecretKeyShare(signer).sign(candidate),
+                        };
+                        o.broadcast(&left, &membership::Msg::VoteJoin(vote).into());
+
+                        let forged_vote = membership::VoteJoin {
+                            candidate: forged_candidate,
+                            sig_share: fake_crypto::SecretKeyShare(signer).sign(&forged_candidate),
+                        };
+                        o.broadcast(&right, &membership::Msg::VoteJoin(forged_vote).into());
+                    }
+                }
+                Msg::Wallet(ledger::Msg::ReqReissue(tx)) => {
+                    if let Some(input) = tx.inputs.first().copied() {
+                        if state.wallet.ledger.is_unspent(&input) {
+                            let amount = input.amount();
+                            let tx_left = ledger::build_tx(vec![input], vec![amount]);
+                            let tx_right = ledger::build_tx(vec![input], vec![0, amount]);
+
+                            for &signer in &elders {
+                                let vote_left = ledger::Msg::VoteReissue {
+                                    tx: tx_left.clone(),
+                                    sig_share: fake_crypto::SecretKeyShare(signer).sign(&tx_left),
+                                };
+                                o.broadcast(&left, &vote_left.into());
+
+                                let vote_right = ledger::Msg::VoteReissue {
+                                    tx: tx_right.clone(),
+                                    sig_share: fake_crypto::SecretKeyShare(signer).sign(&tx_right),
+                                };
+                                o.broadcast(&right, &vote_right.into());
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        false
+    }
+}
+
 #[derive(Clone)]
 struct ModelCfg {
     elder_count: usize,
     server_count: usize,
     network: Network<<Node as Actor>::Msg>,
+    adversary_cfg: AdversaryCfg,
+    time_cfg: time::Config,
 }
 
 fn prop_stable_set_converged(state: &ActorModelState<Node, Vec<Msg>>) -> bool {
@@ -152,13 +314,140 @@ fn prop_unspent_outputs_equals_genesis_amount(state: &ActorModelState<Node, Vec<
     })
 }
 
+/// No two honest actors ever finalize different stable sets of the same
+/// size: once a size is reached by one honest actor, every other honest
+/// actor at that same size must agree on its members. The adversary is
+/// excluded since it is expected to equivocate by construction.
+fn prop_honest_stable_sets_never_conflict(
+    cfg: &ModelCfg,
+    state: &ActorModelState<Node, Vec<Msg>>,
+) -> bool {
+    let mut by_len: BTreeMap<usize, &stable_set::StableSet> = BTreeMap::new();
+
+    state
+        .actor_states
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !cfg.adversary_cfg.is_adversary(Id::from(*i)))
+        .all(|(_, actor)| {
+            let stable_set = &actor.membership.stable_set;
+            match by_len.insert(stable_set.ids().count(), stable_set) {
+                Some(existing) => existing == stable_set,
+                None => true,
+            }
+        })
+}
+
+/// Same as "Never two nodes aggregate a double spend", but restricted to
+/// honest actors so an adversary that equivocates on purpose can't trip
+/// the property on its own behalf.
+fn prop_honest_never_aggregate_conflicting_spend(
+    cfg: &ModelCfg,
+    state: &ActorModelState<Node, Vec<Msg>>,
+) -> bool {
+    let concurrent_txs = BTreeSet::from_iter(
+        state
+            .actor_states
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !cfg.adversary_cfg.is_adversary(Id::from(*i)))
+            .filter_map(|(_, a)| a.wallet.pending_tx.clone())
+            .filter(|(tx, sig)| sig.verify(&sig.voters, tx))
+            .map(|(tx, _)| tx),
+    );
+
+    concurrent_txs.len() <= 1
+}
+
+/// No two distinct finalized transactions ever reveal the same nullifier,
+/// i.e. no input coin is ever accepted as spent by two different
+/// transactions, however votes interleave.
+fn prop_no_nullifier_reuse(state: &ActorModelState<Node, Vec<Msg>>) -> bool {
+    let mut spent_by: BTreeMap<ledger::Nullifier, ledger::Tx> = BTreeMap::new();
+
+    state
+        .actor_states
+        .iter()
+        .filter_map(|a| a.wallet.pending_tx.clone())
+        .filter(|(tx, sig)| sig.verify(&sig.voters, tx))
+        .all(|(tx, _)| {
+            tx.inputs.iter().all(|input| match spent_by.insert(input.nullifier(), tx.clone()) {
+                Some(prev_tx) => prev_tx == tx,
+                None => true,
+            })
+        })
+}
+
+/// Across all honest nodes, any given input nullifier maps to at most one
+/// distinct spend proof: the spend book's persistent, queryable record is
+/// no weaker a guarantee than the transient `pending_tx` check above.
+fn prop_consistent_spend_book(cfg: &ModelCfg, state: &ActorModelState<Node, Vec<Msg>>) -> bool {
+    let mut seen: BTreeMap<ledger::Nullifier, ledger::SpendProof> = BTreeMap::new();
+
+    state
+        .actor_states
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !cfg.adversary_cfg.is_adversary(Id::from(*i)))
+        .all(|(_, actor)| {
+            actor
+                .wallet
+                .ledger
+                .spend_book_entries()
+                .all(|(nullifier, proof)| match seen.insert(*nullifier, proof.clone()) {
+                    Some(prev) => prev == *proof,
+                    None => true,
+                })
+        })
+}
+
+/// Every unspent commitment corresponds to exactly one live coin.
+fn prop_unique_unspent_commitments(state: &ActorModelState<Node, Vec<Msg>>) -> bool {
+    state
+        .actor_states
+        .iter()
+        .all(|actor| actor.wallet.ledger.has_unique_unspent_commitments())
+}
+
+/// The Common Prefix property: truncating every honest actor's membership
+/// history to `security_param_k` slots behind the shortest honest
+/// history's tip must leave byte-identical prefixes, i.e. churn
+/// concurrency can only ever affect
+/// the unstable suffix and finalized membership can never be rolled back.
+fn prop_common_prefix(cfg: &ModelCfg, state: &ActorModelState<Node, Vec<Msg>>) -> bool {
+    let k = cfg.time_cfg.security_param_k as usize;
+
+    let honest_histories: Vec<&[(time::Slot, stable_set::StableSet)]> = state
+        .actor_states
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !cfg.adversary_cfg.is_adversary(Id::from(*i)))
+        .map(|(_, actor)| actor.membership.history.as_slice())
+        .collect();
+
+    // Actors tick independently and unsynchronized, so they can be at
+    // wildly different history lengths at the same model state; comparing
+    // full prefixes of unequal length would flag normal async scheduling
+    // as a reorg. Only the portion every honest actor has necessarily
+    // reached - up to `k` slots behind the shortest honest history - is
+    // eligible to be considered final, so that's the only range compared.
+    let Some(shortest) = honest_histories.iter().map(|history| history.len()).min() else {
+        return true;
+    };
+    let bound = shortest.saturating_sub(k);
+
+    let reference = &honest_histories[0][..bound];
+    honest_histories.iter().all(|history| &history[..bound] == reference)
+}
 
 impl ModelCfg {
     fn into_model(self) -> ActorModel<Node, Self, Vec<Msg>> {
         ActorModel::new(self.clone(), vec![])
             .actors((0..self.server_count).map(|i| Node {
-                genesis_nodes: BTreeSet::from_iter((0..self.elder_count).into_iter().map(Id::from)),
+                genesis_nodes: BTreeSet::from_iter((0..self.elder_count).map(Id::from)),
                 peers: model_peers(i, self.server_count),
+                adversary_cfg: self.adversary_cfg.clone(),
+                time_cfg: self.time_cfg,
             }))
             .init_network(self.network)
             .property(
@@ -190,7 +479,34 @@ impl ModelCfg {
                     concurrent_txs.len() <= 1
                 },
             )
-
+            .property(
+                Expectation::Always,
+                "honest nodes never finalize conflicting stable sets under equivocation",
+                prop_honest_stable_sets_never_conflict,
+            )
+            .property(
+                Expectation::Always,
+                "honest nodes never aggregate a conflicting spend under equivocation",
+                prop_honest_never_aggregate_conflicting_spend,
+            )
+            .property(Expectation::Always, "common prefix", |cfg, state| {
+                prop_common_prefix(cfg, state)
+            })
+            .property(
+                Expectation::Always,
+                "no two finalized transactions reuse a nullifier",
+                |_, state| prop_no_nullifier_reuse(state),
+            )
+            .property(
+                Expectation::Always,
+                "honest spend books never disagree on an input's spend proof",
+                prop_consistent_spend_book,
+            )
+            .property(
+                Expectation::Always,
+                "every unspent commitment maps to exactly one live coin",
+                |_, state| prop_unique_unspent_commitments(state),
+            )
     }
 }
 
@@ -200,9 +516,22 @@ fn main() {
     let network = Network::new_unordered_nonduplicating([]);
 
     ModelCfg {
-        elder_count: 1,
+        // More than one elder so `Node::split_elders` can hand the adversary
+        // two non-empty, disjoint elder subsets to equivocate across.
+        elder_count: 3,
         server_count: 5,
         network,
+        adversary_cfg: AdversaryCfg {
+            adversaries: BTreeSet::from([Id::from(0)]),
+            actions: BTreeSet::from([
+                AdversaryAction::Equivocate,
+                AdversaryAction::Replay,
+                AdversaryAction::DropOrDelay,
+            ]),
+            // Elder 1 is the node whose messages the adversary drops.
+            target: Some(Id::from(1)),
+        },
+        time_cfg: time::Config::default(),
     }
     .into_model()
     .checker()
@@ -0,0 +1,134 @@
+//! BFT membership: elders vote candidates into the [`StableSet`].
+//!
+//! A prospective member sends [`Msg::ReqJoin`] to the genesis elders. Each
+//! elder that sees the request casts a [`VoteJoin`] to every elder, itself
+//! included.
+//! Once an elder observes a supermajority of votes for the same candidate,
+//! the candidate is queued as a pending delta rather than admitted on the
+//! spot; [`Membership::tick`] batches all deltas accumulated during a slot
+//! into the next slot-stamped `stable_set` snapshot, appended to `history`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use stateright::actor::{Id, Out};
+
+use crate::fake_crypto::{is_super_majority, SecretKeyShare, SignatureShare};
+use crate::stable_set::StableSet;
+use crate::time::{self, Slot};
+use crate::Node;
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Msg {
+    ReqJoin(Id),
+    VoteJoin(VoteJoin),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct VoteJoin {
+    pub candidate: Id,
+    pub sig_share: SignatureShare,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Membership {
+    pub stable_set: StableSet,
+    /// Slot-stamped snapshots of `stable_set`, oldest first. Everything
+    /// more than `security_param_k` slots behind the tip is final.
+    pub history: Vec<(Slot, StableSet)>,
+    current_slot: Slot,
+    /// Candidates admitted by quorum this slot, not yet folded into
+    /// `stable_set`.
+    pending_deltas: BTreeSet<Id>,
+    votes: BTreeMap<Id, BTreeSet<Id>>,
+    /// `stable_set` as observed at the end of the previous `on_msg` call,
+    /// used to check monotonicity across calls rather than against a
+    /// value captured earlier in the same call.
+    prev_stable_set: StableSet,
+}
+
+impl Membership {
+    pub fn new(genesis_nodes: &BTreeSet<Id>) -> Self {
+        let mut stable_set = StableSet::default();
+        for &id in genesis_nodes {
+            stable_set.add(id);
+        }
+
+        Self {
+            stable_set: stable_set.clone(),
+            history: vec![(Slot::default(), stable_set.clone())],
+            current_slot: Slot::default(),
+            pending_deltas: BTreeSet::new(),
+            votes: BTreeMap::new(),
+            prev_stable_set: stable_set,
+        }
+    }
+
+    /// Advances to the next slot, folding any deltas accumulated this slot
+    /// into `stable_set` and appending the result to `history`.
+    pub fn tick(&mut self, cfg: &time::Config) {
+        let members_before_tick = self.stable_set.members().count();
+        let epoch_before = cfg.epoch_of(self.current_slot);
+
+        self.current_slot = self.current_slot.next();
+        for candidate in std::mem::take(&mut self.pending_deltas) {
+            self.stable_set.add(candidate);
+        }
+        self.history.push((self.current_slot, self.stable_set.clone()));
+
+        crate::invariant!(
+            self.stable_set.members().count() >= members_before_tick,
+            "the stable set must never shrink"
+        );
+        let epoch_after = cfg.epoch_of(self.current_slot);
+        crate::invariant!(
+            epoch_after.0 == epoch_before.0 || epoch_after.0 == epoch_before.0 + 1,
+            "a single tick must advance the epoch by at most one"
+        );
+    }
+
+    pub fn elders(&self) -> BTreeSet<Id> {
+        self.stable_set.members().collect()
+    }
+
+    pub fn current_slot(&self) -> Slot {
+        self.current_slot
+    }
+
+    pub fn req_join(&self, candidate: Id) -> Msg {
+        Msg::ReqJoin(candidate)
+    }
+
+    pub fn on_msg(
+        &mut self,
+        elders: &BTreeSet<Id>,
+        id: Id,
+        _src: Id,
+        msg: Msg,
+        o: &mut Out<Node>,
+    ) {
+        match msg {
+            Msg::ReqJoin(candidate) => {
+                if elders.contains(&id) {
+                    let sig_share = SecretKeyShare(id).sign(&candidate);
+                    o.broadcast(elders, &Msg::VoteJoin(VoteJoin { candidate, sig_share }).into());
+                }
+            }
+            Msg::VoteJoin(vote) => {
+                if elders.contains(&id) && !self.stable_set.contains(vote.candidate) {
+                    let voters = self.votes.entry(vote.candidate).or_default();
+                    voters.insert(vote.sig_share.signer);
+                    if is_super_majority(voters.len(), elders.len()) {
+                        self.pending_deltas.insert(vote.candidate);
+                        self.votes.remove(&vote.candidate);
+                    }
+                }
+            }
+        }
+
+        crate::invariant!(
+            self.prev_stable_set.members().all(|m| self.stable_set.contains(m)),
+            "stable_set must never drop a member present at the previous on_msg call"
+        );
+        self.prev_stable_set = self.stable_set.clone();
+    }
+}
@@ -0,0 +1,32 @@
+//! The stable set: the membership's converged view of who has joined.
+//!
+//! Membership is monotonic in this model (no leaves yet), so a `StableSet`
+//! is simply the set of `Id`s that have been admitted by a supermajority of
+//! elders.
+
+use std::collections::BTreeSet;
+
+use stateright::actor::Id;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct StableSet {
+    members: BTreeSet<Id>,
+}
+
+impl StableSet {
+    pub fn contains(&self, id: Id) -> bool {
+        self.members.contains(&id)
+    }
+
+    pub fn add(&mut self, id: Id) {
+        self.members.insert(id);
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = Id> + '_ {
+        self.members.iter().copied()
+    }
+
+    pub fn members(&self) -> impl Iterator<Item = Id> + '_ {
+        self.members.iter().copied()
+    }
+}
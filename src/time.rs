@@ -0,0 +1,56 @@
+//! Slot/epoch timekeeping for the membership's churn history.
+//!
+//! Membership changes are batched per [`Slot`] rather than applied the
+//! instant a vote reaches quorum, so [`crate::membership::Membership`]
+//! keeps an ordered history of slot-stamped [`crate::stable_set::StableSet`]
+//! snapshots instead of a single ever-growing set. Anything older than
+//! `security_param_k` slots from the tip is considered final: see
+//! `prop_common_prefix` in `main`.
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Slot(pub u64);
+
+impl Slot {
+    pub fn next(self) -> Self {
+        Slot(self.0 + 1)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Epoch(pub u64);
+
+/// Parameters governing how slots group into epochs.
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// Slots from the tip after which history is considered final and may
+    /// never be reorganized (the Common Prefix security parameter).
+    pub security_param_k: u32,
+    /// Fraction of slots in which an elder is actually active.
+    pub active_slot_coeff: f64,
+    /// Slot at which a node stops scheduling its own `Msg::Tick`. Without
+    /// a cap each tick would push another entry onto `Membership::history`
+    /// forever, making the model's state space infinite and the
+    /// exhaustive checker never terminate.
+    pub max_slot: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            security_param_k: 2,
+            active_slot_coeff: 0.5,
+            max_slot: 8,
+        }
+    }
+}
+
+impl Config {
+    /// Size, in slots, of one epoch: `floor(k / f)`.
+    pub fn epoch_len(&self) -> u64 {
+        (self.security_param_k as f64 / self.active_slot_coeff).floor() as u64
+    }
+
+    pub fn epoch_of(&self, slot: Slot) -> Epoch {
+        Epoch(slot.0 / self.epoch_len().max(1))
+    }
+}